@@ -56,22 +56,38 @@ assert!(!stft.is_empty())
 ```
 */
 
+use std::collections::VecDeque;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use realfft::RealToComplex;
 use rustfft::num_complex::Complex;
 use rustfft::num_traits::{Float, Signed, Zero};
 use rustfft::{FFTnum, FFTplanner, FFT};
 
 use strider::{SliceRing, SliceRingImpl};
 
+/// Which fft implementation backs an [`STFT`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FftBackend {
+    /// `realfft`-based real-to-complex transform producing the full
+    /// `window_size / 2 + 1` non-redundant bins (including Nyquist). Roughly
+    /// twice as fast as the complex path for real input.
+    RealToComplex,
+    /// The original full complex transform, producing `window_size / 2` bins.
+    /// Kept for callers that depend on the historical bin count.
+    Complex,
+}
+
 pub struct STFT<T>
 where
     T: FFTnum + FromF64 + Float,
 {
     pub window_size: usize,
     pub step_size: usize,
+    pub backend: FftBackend,
     pub fft: Arc<dyn FFT<T>>,
+    pub r2c: Option<RealToComplex<T>>,
     pub window: Option<Vec<T>>,
     pub sample_ring: SliceRingImpl<T>,
     pub real_input: Vec<T>,
@@ -84,12 +100,63 @@ where
     T: FFTnum + FromF64 + Float,
 {
     pub fn new(window_type: WindowType, window_size: usize, step_size: usize) -> Self {
-        let window = Self::window_type_to_window_vec(window_type, window_size);
-        Self::new_with_window_vec(window, window_size, step_size)
+        Self::new_with_backend(window_type, window_size, step_size, FftBackend::RealToComplex)
+    }
+
+    /// Like [`new`](Self::new) but keeps the historical full complex fft path,
+    /// so the column bin count stays at `window_size / 2`.
+    pub fn new_with_complex_fft(
+        window_type: WindowType,
+        window_size: usize,
+        step_size: usize,
+    ) -> Self {
+        Self::new_with_backend(window_type, window_size, step_size, FftBackend::Complex)
+    }
+
+    pub fn new_with_backend(
+        window_type: WindowType,
+        window_size: usize,
+        step_size: usize,
+        backend: FftBackend,
+    ) -> Self {
+        let window = Self::window_type_to_window_vec(window_type, window_size, false);
+        Self::new_with_window_vec(window, window_size, step_size, backend)
+    }
+
+    /// Like [`new`](Self::new) but generates the *periodic* (DFT-even) form of
+    /// the window (e.g. Hann as `0.5 - 0.5*cos(2πi/N)` over `i in 0..N`), so
+    /// overlapping frames tile correctly and the spectra match numpy / librosa
+    /// / TensorFlow.
+    pub fn new_periodic(window_type: WindowType, window_size: usize, step_size: usize) -> Self {
+        let window = Self::window_type_to_window_vec(window_type, window_size, true);
+        Self::new_with_window_vec(window, window_size, step_size, FftBackend::RealToComplex)
+    }
+
+    /// Builds an `STFT` whose window is produced by calling `f(i, window_size)`
+    /// for every index `i in 0..window_size`, so callers can supply Kaiser,
+    /// Gaussian, flat-top or any other analytic window.
+    pub fn new_with_window_fn<F>(f: F, window_size: usize, step_size: usize) -> Self
+    where
+        F: Fn(usize, usize) -> T,
+    {
+        let window = (0..window_size).map(|i| f(i, window_size)).collect();
+        Self::new_with_window_vec(Some(window), window_size, step_size, FftBackend::RealToComplex)
+    }
+
+    /// Builds an `STFT` from a caller-provided window, for measured or
+    /// precomputed window functions.
+    /// # Panics
+    /// panics unless `window.len() == window_size`
+    pub fn new_with_window_slice(window: Vec<T>, window_size: usize, step_size: usize) -> Self {
+        assert_eq!(window.len(), window_size);
+        Self::new_with_window_vec(Some(window), window_size, step_size, FftBackend::RealToComplex)
     }
 
     pub fn output_size(&self) -> usize {
-        self.window_size / 2
+        match self.backend {
+            FftBackend::RealToComplex => self.window_size / 2 + 1,
+            FftBackend::Complex => self.window_size / 2,
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -146,6 +213,138 @@ where
         }
     }
 
+    /// Computes a mel-scaled column by multiplying the power spectrum
+    /// (`|X_k|^2`) of the current frame with the triangular filters of `mel`.
+    /// # Panics
+    /// panics unless `mel.n_mels == output.len()` and the bank was built for an
+    /// STFT with this one's `output_size()` (so its bin indices are in range)
+    pub fn compute_mel_column(&mut self, mel: &MelFilterBank<T>, output: &mut [T]) {
+        assert_eq!(mel.n_mels, output.len());
+        assert_eq!(mel.output_size, self.output_size());
+
+        self.compute_into_complex_output();
+
+        for (dst, filter) in output.iter_mut().zip(mel.filters.iter()) {
+            let mut acc = T::zero();
+            for &(bin, weight) in filter.iter() {
+                acc = acc + weight * self.complex_output[bin].norm().powi(2);
+            }
+            *dst = acc;
+        }
+    }
+
+    /// Like [`compute_mel_column`](Self::compute_mel_column) but takes the
+    /// natural logarithm of every mel energy, producing log-mel features
+    /// directly. Energies are floored at a small positive value to keep the
+    /// logarithm finite.
+    /// # Panics
+    /// panics unless `mel.n_mels == output.len()`
+    pub fn compute_log_mel_column(&mut self, mel: &MelFilterBank<T>, output: &mut [T]) {
+        self.compute_mel_column(mel, output);
+
+        let floor = T::from_f64(1e-10);
+        for dst in output.iter_mut() {
+            *dst = dst.max(floor).ln();
+        }
+    }
+
+    /// Transforms a whole signal at once into a column matrix of log-magnitude
+    /// spectra, one `Vec<T>` per frame in frame order. Frames are independent,
+    /// so the work is split across `threads` worker threads (use `1` for a
+    /// deterministic, single-threaded pass). Returns an empty matrix when
+    /// `samples` is shorter than `window_size`.
+    pub fn compute_all(&self, samples: &[T], threads: usize) -> Vec<Vec<T>>
+    where
+        T: Send + Sync,
+    {
+        self.compute_all_raw(samples, threads)
+            .into_iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|c| log10_positive(c.norm()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Like [`compute_all`](Self::compute_all) but keeps the raw complex bins.
+    pub fn compute_all_complex(&self, samples: &[T], threads: usize) -> Vec<Vec<Complex<T>>>
+    where
+        T: Send + Sync,
+    {
+        self.compute_all_raw(samples, threads)
+    }
+
+    /// Shared worker for the batch transforms. Computes every frame of
+    /// `samples` with the complex fft plan (cloned cheaply per thread) and
+    /// returns the `output_size()` non-redundant bins of each.
+    fn compute_all_raw(&self, samples: &[T], threads: usize) -> Vec<Vec<Complex<T>>>
+    where
+        T: Send + Sync,
+    {
+        if samples.len() < self.window_size {
+            return vec![];
+        }
+
+        let out_size = self.output_size();
+        let n_frames = 1 + (samples.len() - self.window_size) / self.step_size;
+        let threads = threads.max(1).min(n_frames);
+        let per = (n_frames + threads - 1) / threads;
+
+        // Contiguous frame ranges, each paired with the owned sample span it
+        // needs (frames overlap, so the spans overlap by `window_size - step_size`).
+        let mut ranges = Vec::with_capacity(threads);
+        for t in 0..threads {
+            let start = t * per;
+            if start >= n_frames {
+                break;
+            }
+            let end = ((t + 1) * per).min(n_frames);
+            let span_start = start * self.step_size;
+            let span_end = (end - 1) * self.step_size + self.window_size;
+            ranges.push((samples[span_start..span_end].to_vec(), end - start));
+        }
+
+        let mut result = Vec::with_capacity(n_frames);
+        if threads <= 1 {
+            for (span, frame_count) in ranges {
+                result.extend(transform_span(
+                    &span,
+                    &self.window,
+                    self.window_size,
+                    self.step_size,
+                    out_size,
+                    frame_count,
+                    &self.fft,
+                ));
+            }
+        } else {
+            let mut handles = Vec::with_capacity(ranges.len());
+            for (span, frame_count) in ranges {
+                let fft = Arc::clone(&self.fft);
+                let window = self.window.clone();
+                let window_size = self.window_size;
+                let step_size = self.step_size;
+                handles.push(std::thread::spawn(move || {
+                    transform_span(
+                        &span,
+                        &window,
+                        window_size,
+                        step_size,
+                        out_size,
+                        frame_count,
+                        &fft,
+                    )
+                }));
+            }
+            for handle in handles {
+                result.extend(handle.join().unwrap());
+            }
+        }
+        result
+    }
+
     /// Make a step
     /// Drops `self.step_size` samples from the internal buffer `self.sample_ring`.
     pub fn move_to_next_column(&mut self) {
@@ -153,48 +352,80 @@ where
     }
 
     // TODO this should ideally take an iterator and not a vec
-    fn new_with_window_vec(window: Option<Vec<T>>, window_size: usize, step_size: usize) -> Self {
+    fn new_with_window_vec(
+        window: Option<Vec<T>>,
+        window_size: usize,
+        step_size: usize,
+        backend: FftBackend,
+    ) -> Self {
         // TODO more assertions:
         // window_size is power of two
         // step_size > 0
         assert!(step_size <= window_size);
         let inverse = false;
         let mut planner = FFTplanner::new(inverse);
+        // The real-to-complex backend does not need the `complex_input`
+        // scratch buffer at all, so leave it empty in that case.
+        let (r2c, complex_input) = match backend {
+            FftBackend::RealToComplex => (Some(RealToComplex::<T>::new(window_size).unwrap()), vec![]),
+            FftBackend::Complex => (
+                None,
+                std::iter::repeat(Complex::<T>::zero())
+                    .take(window_size)
+                    .collect(),
+            ),
+        };
         STFT {
             window_size,
             step_size,
+            backend,
             fft: planner.plan_fft(window_size),
+            r2c,
             sample_ring: SliceRingImpl::new(),
             window,
             real_input: std::iter::repeat(T::zero()).take(window_size).collect(),
-            complex_input: std::iter::repeat(Complex::<T>::zero())
-                .take(window_size)
-                .collect(),
+            complex_input,
             complex_output: std::iter::repeat(Complex::<T>::zero())
                 .take(window_size)
                 .collect(),
         }
     }
 
-    fn window_type_to_window_vec(window_type: WindowType, window_size: usize) -> Option<Vec<T>> {
+    /// Builds the window vector for `window_type`. When `periodic` is set the
+    /// DFT-even form is produced by generating the symmetric window of length
+    /// `window_size + 1` and dropping the duplicated endpoint.
+    fn window_type_to_window_vec(
+        window_type: WindowType,
+        window_size: usize,
+        periodic: bool,
+    ) -> Option<Vec<T>> {
+        let len = if periodic {
+            window_size + 1
+        } else {
+            window_size
+        };
         match window_type {
             WindowType::Hanning => Some(
-                apodize::hanning_iter(window_size)
+                apodize::hanning_iter(len)
+                    .take(window_size)
                     .map(FromF64::from_f64)
                     .collect(),
             ),
             WindowType::Hamming => Some(
-                apodize::hamming_iter(window_size)
+                apodize::hamming_iter(len)
+                    .take(window_size)
                     .map(FromF64::from_f64)
                     .collect(),
             ),
             WindowType::Blackman => Some(
-                apodize::blackman_iter(window_size)
+                apodize::blackman_iter(len)
+                    .take(window_size)
                     .map(FromF64::from_f64)
                     .collect(),
             ),
             WindowType::Nuttall => Some(
-                apodize::nuttall_iter(window_size)
+                apodize::nuttall_iter(len)
+                    .take(window_size)
                     .map(FromF64::from_f64)
                     .collect(),
             ),
@@ -215,14 +446,440 @@ where
             }
         }
 
-        // Copy windowed real_input as real parts into complex_input
-        for (dst, src) in self.complex_input.iter_mut().zip(self.real_input.iter()) {
-            dst.re = *src;
+        match self.r2c {
+            // Real-to-complex transform straight into the non-redundant bins.
+            Some(ref mut r2c) => {
+                let bins = self.window_size / 2 + 1;
+                r2c.process(&mut self.real_input, &mut self.complex_output[..bins])
+                    .unwrap();
+            }
+            // Copy windowed real_input as real parts into complex_input and run
+            // the full complex fft.
+            None => {
+                for (dst, src) in self.complex_input.iter_mut().zip(self.real_input.iter()) {
+                    dst.re = *src;
+                }
+                self.fft
+                    .process(&mut self.complex_input, &mut self.complex_output);
+            }
         }
+    }
+}
 
-        // Compute fft
-        self.fft
+/// The inverse of [`STFT`]: consumes the `Complex<T>` columns produced by
+/// [`STFT::compute_complex_column`] and performs overlap-add resynthesis back
+/// into a time-domain signal.
+///
+/// Each pushed column holds the [`ISTFT::input_size`] non-redundant bins
+/// (`window_size / 2 + 1` for the default backend, `window_size / 2` for
+/// [`FftBackend::Complex`]). They are
+/// Hermitian-mirrored back to a full `window_size` spectrum, run through an
+/// inverse fft, multiplied by the synthesis window and accumulated into an
+/// internal buffer, advancing by `step_size` per frame. To undo the analysis
+/// (and synthesis) windowing the overlap-added samples are divided by the
+/// per-sample sum of squared window values before they are emitted, so the
+/// reconstruction is exact at every sample where that sum is non-zero.
+pub struct ISTFT<T>
+where
+    T: FFTnum + FromF64 + Float,
+{
+    pub window_size: usize,
+    pub step_size: usize,
+    pub backend: FftBackend,
+    pub ifft: Arc<dyn FFT<T>>,
+    pub window: Option<Vec<T>>,
+    pub complex_input: Vec<Complex<T>>,
+    pub complex_output: Vec<Complex<T>>,
+    /// overlap-added time-domain samples, front = oldest not-yet-popped sample
+    accumulator: VecDeque<T>,
+    /// running per-sample sum of squared window values, parallel to `accumulator`
+    window_acc: VecDeque<T>,
+    /// offset (from the front of `accumulator`) where the next frame is added;
+    /// everything before it is finished and ready to pop
+    frame_start: usize,
+}
+
+impl<T> ISTFT<T>
+where
+    T: FFTnum + FromF64 + Float,
+{
+    pub fn new(window_type: WindowType, window_size: usize, step_size: usize) -> Self {
+        Self::new_with_backend(window_type, window_size, step_size, FftBackend::RealToComplex)
+    }
+
+    /// Like [`new`](Self::new) but pairs with the historical complex fft
+    /// backend, expecting `window_size / 2` bins per column.
+    pub fn new_with_complex_fft(
+        window_type: WindowType,
+        window_size: usize,
+        step_size: usize,
+    ) -> Self {
+        Self::new_with_backend(window_type, window_size, step_size, FftBackend::Complex)
+    }
+
+    /// Builds an `ISTFT` whose expected column bin count matches `backend`, so
+    /// it composes with an [`STFT`] configured the same way.
+    pub fn new_with_backend(
+        window_type: WindowType,
+        window_size: usize,
+        step_size: usize,
+        backend: FftBackend,
+    ) -> Self {
+        let window = STFT::<T>::window_type_to_window_vec(window_type, window_size, false);
+        Self::new_with_window_vec(window, window_size, step_size, backend)
+    }
+
+    /// The number of complex bins each pushed column must contain, mirroring
+    /// [`STFT::output_size`] for the matching backend.
+    pub fn input_size(&self) -> usize {
+        match self.backend {
+            FftBackend::RealToComplex => self.window_size / 2 + 1,
+            FftBackend::Complex => self.window_size / 2,
+        }
+    }
+
+    /// Hermitian-mirrors a half spectrum back to a full `window_size` spectrum,
+    /// runs the inverse fft and overlap-adds one windowed frame.
+    ///
+    /// # Panics
+    /// panics unless `self.input_size() == column.len()`
+    pub fn push_complex_column(&mut self, column: &[Complex<T>]) {
+        assert_eq!(self.input_size(), column.len());
+
+        let half = self.window_size / 2;
+
+        // Rebuild the full Hermitian-symmetric spectrum from the provided bins.
+        // Under the `RealToComplex` backend the column carries the real Nyquist
+        // bin (`column[half]`); under the `Complex` backend it is absent and
+        // treated as zero.
+        for (dst, src) in self.complex_input[..column.len()].iter_mut().zip(column.iter()) {
+            *dst = *src;
+        }
+        if self.backend == FftBackend::Complex {
+            self.complex_input[half] = Complex::<T>::zero();
+        }
+        for k in (half + 1)..self.window_size {
+            self.complex_input[k] = column[self.window_size - k].conj();
+        }
+
+        self.ifft
             .process(&mut self.complex_input, &mut self.complex_output);
+
+        // Make sure the accumulator reaches the end of this frame.
+        let end = self.frame_start + self.window_size;
+        while self.accumulator.len() < end {
+            self.accumulator.push_back(T::zero());
+            self.window_acc.push_back(T::zero());
+        }
+
+        // rustfft's inverse transform is unnormalized; fold the `1 / N` here.
+        let norm = T::from_f64(self.window_size as f64);
+        for i in 0..self.window_size {
+            let mut sample = self.complex_output[i].re / norm;
+            let w = match self.window {
+                Some(ref window) => window[i],
+                None => T::one(),
+            };
+            sample = sample * w;
+            let idx = self.frame_start + i;
+            self.accumulator[idx] = self.accumulator[idx] + sample;
+            self.window_acc[idx] = self.window_acc[idx] + w * w;
+        }
+
+        // Every sample before `frame_start + step_size` is now final: no later
+        // frame starts before that offset, so none overlaps it.
+        self.frame_start += self.step_size;
+    }
+
+    /// Drains the samples that are no longer overlapped by future frames into
+    /// `out`, dividing each by its window-normalization factor. Returns the
+    /// number of samples written.
+    pub fn pop_samples(&mut self, out: &mut [T]) -> usize {
+        let n = out.len().min(self.frame_start);
+        for dst in out[..n].iter_mut() {
+            let sample = self.accumulator.pop_front().unwrap();
+            let norm = self.window_acc.pop_front().unwrap();
+            *dst = if norm.is_zero() {
+                T::zero()
+            } else {
+                sample / norm
+            };
+        }
+        self.frame_start -= n;
+        n
+    }
+
+    /// Checks the constant-overlap-add (COLA) condition for the active window
+    /// and `step_size`: whether the squared window sums to the same constant at
+    /// every output sample across all overlapping frames. Resynthesis does not
+    /// depend on it, since [`pop_samples`](Self::pop_samples) divides each
+    /// sample by its own window sum.
+    pub fn is_cola(&self) -> bool {
+        let squared: Vec<T> = match self.window {
+            Some(ref window) => window.iter().map(|w| *w * *w).collect(),
+            None => std::iter::repeat(T::one()).take(self.window_size).collect(),
+        };
+        let mut sums = vec![T::zero(); self.step_size];
+        for (i, w) in squared.iter().enumerate() {
+            let bucket = i % self.step_size;
+            sums[bucket] = sums[bucket] + *w;
+        }
+        let max = sums.iter().cloned().fold(T::zero(), T::max);
+        let min = sums.iter().cloned().fold(max, T::min);
+        if max.is_zero() {
+            return false;
+        }
+        (max - min) / max < T::from_f64(1e-6)
+    }
+
+    fn new_with_window_vec(
+        window: Option<Vec<T>>,
+        window_size: usize,
+        step_size: usize,
+        backend: FftBackend,
+    ) -> Self {
+        assert!(step_size <= window_size);
+        let inverse = true;
+        let mut planner = FFTplanner::new(inverse);
+        ISTFT {
+            window_size,
+            step_size,
+            backend,
+            ifft: planner.plan_fft(window_size),
+            window,
+            complex_input: std::iter::repeat(Complex::<T>::zero())
+                .take(window_size)
+                .collect(),
+            complex_output: std::iter::repeat(Complex::<T>::zero())
+                .take(window_size)
+                .collect(),
+            accumulator: VecDeque::new(),
+            window_acc: VecDeque::new(),
+            frame_start: 0,
+        }
+    }
+}
+
+/// A precomputed triangular mel-frequency filterbank mapping the linear-
+/// frequency power spectrum of an [`STFT`] onto `n_mels` mel bands, as used by
+/// Whisper-style and other ML audio frontends.
+///
+/// Each filter is stored sparsely as the `(bin, weight)` pairs it touches, so
+/// the whole bank is a sparse `n_mels × output_size` matrix.
+pub struct MelFilterBank<T> {
+    pub n_mels: usize,
+    /// number of linear-frequency bins the bank expects, matching the source
+    /// [`STFT::output_size`]
+    pub output_size: usize,
+    pub filters: Vec<Vec<(usize, T)>>,
+}
+
+impl<T> MelFilterBank<T>
+where
+    T: FromF64 + FFTnum + Float,
+{
+    /// Builds a filterbank matched to `stft`, taking its `output_size()` (and
+    /// therefore its active backend) as the number of linear bins so the two
+    /// always compose without an out-of-range index.
+    pub fn for_stft(
+        stft: &STFT<T>,
+        sample_rate: usize,
+        n_mels: usize,
+        f_min: f64,
+        f_max: f64,
+    ) -> Self {
+        Self::new_with_output_size(
+            stft.window_size,
+            stft.output_size(),
+            sample_rate,
+            n_mels,
+            f_min,
+            f_max,
+        )
+    }
+
+    /// Builds the filterbank for an [`STFT`] of the given `window_size`,
+    /// covering the full `window_size / 2 + 1` bins (including Nyquist) that the
+    /// default [`FftBackend::RealToComplex`] backend produces. Use
+    /// [`for_stft`](Self::for_stft) to match a non-default backend.
+    pub fn new(
+        window_size: usize,
+        sample_rate: usize,
+        n_mels: usize,
+        f_min: f64,
+        f_max: f64,
+    ) -> Self {
+        Self::new_with_output_size(
+            window_size,
+            window_size / 2 + 1,
+            sample_rate,
+            n_mels,
+            f_min,
+            f_max,
+        )
+    }
+
+    fn new_with_output_size(
+        window_size: usize,
+        output_size: usize,
+        sample_rate: usize,
+        n_mels: usize,
+        f_min: f64,
+        f_max: f64,
+    ) -> Self {
+        // `n_mels + 2` equally spaced points in mel space, back to Hz.
+        let mel_min = hz_to_mel(f_min);
+        let mel_max = hz_to_mel(f_max);
+        let bins: Vec<f64> = (0..n_mels + 2)
+            .map(|i| {
+                let mel = mel_min + (mel_max - mel_min) * i as f64 / (n_mels + 1) as f64;
+                // Hz to fractional fft bin index.
+                mel_to_hz(mel) * window_size as f64 / sample_rate as f64
+            })
+            .collect();
+
+        let mut filters = Vec::with_capacity(n_mels);
+        for m in 0..n_mels {
+            let (left, center, right) = (bins[m], bins[m + 1], bins[m + 2]);
+            let mut filter = Vec::new();
+            for bin in 0..output_size {
+                let f = bin as f64;
+                let weight = if f > left && f <= center && center > left {
+                    (f - left) / (center - left)
+                } else if f > center && f < right && right > center {
+                    (right - f) / (right - center)
+                } else {
+                    0.0
+                };
+                if weight > 0.0 {
+                    filter.push((bin, T::from_f64(weight)));
+                }
+            }
+            filters.push(filter);
+        }
+
+        MelFilterBank {
+            n_mels,
+            output_size,
+            filters,
+        }
+    }
+}
+
+/// Converts a frequency in Hz to the mel scale.
+fn hz_to_mel(f: f64) -> f64 {
+    2595.0 * (1.0 + f / 700.0).log10()
+}
+
+/// Converts a mel value back to a frequency in Hz.
+fn mel_to_hz(m: f64) -> f64 {
+    700.0 * (10f64.powf(m / 2595.0) - 1.0)
+}
+
+/// Welch-method averaged power spectral density estimator. Wraps an [`STFT`]
+/// and accumulates the squared magnitude of each column into a running mean,
+/// turning the crate into a spectral-density estimator rather than just a
+/// spectrogram generator.
+pub struct PsdAccumulator<T>
+where
+    T: FFTnum + FromF64 + Float,
+{
+    pub stft: STFT<T>,
+    pub sample_rate: usize,
+    /// count-weighted running mean of `|X_k|^2`
+    mean: Vec<T>,
+    count: usize,
+    scratch: Vec<T>,
+}
+
+impl<T> PsdAccumulator<T>
+where
+    T: FFTnum + FromF64 + Float,
+{
+    pub fn new(stft: STFT<T>, sample_rate: usize) -> Self {
+        let size = stft.output_size();
+        PsdAccumulator {
+            stft,
+            sample_rate,
+            mean: std::iter::repeat(T::zero()).take(size).collect(),
+            count: 0,
+            scratch: std::iter::repeat(T::zero()).take(size).collect(),
+        }
+    }
+
+    /// Computes the next column of the wrapped [`STFT`] and folds its squared
+    /// magnitude into the running mean, then steps forward. Returns `false`
+    /// (without touching the mean) when the ringbuffer holds too few samples.
+    pub fn add_next_column(&mut self) -> bool {
+        if !self.stft.contains_enough_to_compute() {
+            return false;
+        }
+        self.stft.compute_magnitude_column(&mut self.scratch);
+        self.count += 1;
+        let count = T::from_f64(self.count as f64);
+        for (m, s) in self.mean.iter_mut().zip(self.scratch.iter()) {
+            let power = *s * *s;
+            *m = *m + (power - *m) / count;
+        }
+        self.stft.move_to_next_column();
+        true
+    }
+
+    /// Writes the averaged PSD into `output`, applying the standard Welch
+    /// normalization `1 / (sample_rate * sum(window_i^2))` and doubling the
+    /// non-DC, non-Nyquist bins to account for the discarded negative
+    /// frequencies.
+    /// # Panics
+    /// panics unless `self.stft.output_size() == output.len()`
+    pub fn mean_psd(&self, output: &mut [T]) {
+        assert_eq!(self.stft.output_size(), output.len());
+
+        let (_, sum_sq) = self.window_sums();
+        let scale = T::one() / (T::from_f64(self.sample_rate as f64) * sum_sq);
+        let nyquist = self.stft.window_size / 2;
+        for (k, (dst, m)) in output.iter_mut().zip(self.mean.iter()).enumerate() {
+            let mut value = *m * scale;
+            if k != 0 && k != nyquist {
+                value = value + value;
+            }
+            *dst = value;
+        }
+    }
+
+    /// Clears the accumulated mean so a fresh average can be gathered.
+    pub fn reset(&mut self) {
+        for m in self.mean.iter_mut() {
+            *m = T::zero();
+        }
+        self.count = 0;
+    }
+
+    /// The equivalent-noise-bandwidth of the active window,
+    /// `N * sum(w^2) / (sum(w))^2`, used to convert between PSD and band power.
+    pub fn equivalent_noise_bandwidth(&self) -> T {
+        let n = T::from_f64(self.stft.window_size as f64);
+        let (sum, sum_sq) = self.window_sums();
+        n * sum_sq / (sum * sum)
+    }
+
+    /// Returns `(sum(w), sum(w^2))` for the active window, treating a missing
+    /// window as all ones.
+    fn window_sums(&self) -> (T, T) {
+        match self.stft.window {
+            Some(ref window) => {
+                let mut sum = T::zero();
+                let mut sum_sq = T::zero();
+                for w in window.iter() {
+                    sum = sum + *w;
+                    sum_sq = sum_sq + *w * *w;
+                }
+                (sum, sum_sq)
+            }
+            None => {
+                let n = T::from_f64(self.stft.window_size as f64);
+                (n, n)
+            }
+        }
     }
 }
 
@@ -242,7 +899,12 @@ impl FromF64 for f32 {
     }
 }
 
-/// The type of apodization window to use
+/// The type of apodization window to use.
+///
+/// Each variant selects the *symmetric* form of the window. The *periodic*
+/// (DFT-even) form is not a separate variant: it is selected per-instance via
+/// [`STFT::new_periodic`], so the variant set (and its [`FromStr`]/[`Display`]
+/// round-trip) stays unchanged.
 #[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
 pub enum WindowType {
     Hanning,
@@ -275,6 +937,52 @@ impl std::fmt::Display for WindowType {
     }
 }
 
+/// Windows and transforms `frame_count` consecutive frames out of `span` with
+/// a private scratch set, returning the `out_size` non-redundant bins of each.
+/// The batch transforms run this per worker thread so there is no shared
+/// mutable state between frames.
+#[allow(clippy::too_many_arguments)]
+fn transform_span<T>(
+    span: &[T],
+    window: &Option<Vec<T>>,
+    window_size: usize,
+    step_size: usize,
+    out_size: usize,
+    frame_count: usize,
+    fft: &Arc<dyn FFT<T>>,
+) -> Vec<Vec<Complex<T>>>
+where
+    T: FFTnum + Float,
+{
+    let mut real_input: Vec<T> = std::iter::repeat(T::zero()).take(window_size).collect();
+    let mut complex_input: Vec<Complex<T>> = std::iter::repeat(Complex::<T>::zero())
+        .take(window_size)
+        .collect();
+    let mut complex_output: Vec<Complex<T>> = std::iter::repeat(Complex::<T>::zero())
+        .take(window_size)
+        .collect();
+
+    let mut columns = Vec::with_capacity(frame_count);
+    for f in 0..frame_count {
+        let base = f * step_size;
+        for (dst, src) in real_input.iter_mut().zip(span[base..base + window_size].iter()) {
+            *dst = *src;
+        }
+        if let Some(ref w) = window {
+            for (dst, w) in real_input.iter_mut().zip(w.iter()) {
+                *dst = *dst * *w;
+            }
+        }
+        for (dst, src) in complex_input.iter_mut().zip(real_input.iter()) {
+            dst.re = *src;
+            dst.im = T::zero();
+        }
+        fft.process(&mut complex_input, &mut complex_output);
+        columns.push(complex_output[..out_size].to_vec());
+    }
+    columns
+}
+
 /// Returns `0` if `log10(value).is_negative()`,
 /// otherwise returns `log10(value)`.
 /// `log10` turns values in domain `0..1` into values
@@ -331,7 +1039,8 @@ mod tests {
     fn test_stft() {
         let mut stft = STFT::new(WindowType::Hanning, 8, 4);
         assert!(!stft.contains_enough_to_compute());
-        assert_eq!(stft.output_size(), 4);
+        // The real-to-complex backend keeps the Nyquist bin: window_size / 2 + 1.
+        assert_eq!(stft.output_size(), 5);
         assert_eq!(stft.len(), 0);
         stft.append_samples(&vec![500., 0., 100.][..]);
         assert_eq!(stft.len(), 3);
@@ -343,8 +1052,211 @@ mod tests {
         stft.append_samples(&vec![500.][..]);
         assert!(stft.contains_enough_to_compute());
 
-        let mut output: Vec<f64> = vec![0.; 4];
+        let mut output: Vec<f64> = vec![0.; 5];
         stft.compute_column(&mut output[..]);
         println!("{:?}", output);
     }
+
+    /// Looks up the weight a filter assigns to `bin`, or `0` if it is untouched.
+    fn filter_weight(filter: &[(usize, f64)], bin: usize) -> f64 {
+        filter
+            .iter()
+            .find(|(b, _)| *b == bin)
+            .map(|(_, w)| *w)
+            .unwrap_or(0.)
+    }
+
+    #[test]
+    fn test_istft_reconstructs_signal() {
+        let window_size = 16;
+        let step_size = 4;
+        let signal: Vec<f64> = (0..64).map(|i| (i as f64 * 0.3).sin()).collect();
+
+        let mut stft = STFT::new(WindowType::Hanning, window_size, step_size);
+        let mut istft = ISTFT::new(WindowType::Hanning, window_size, step_size);
+        assert_eq!(stft.output_size(), istft.input_size());
+
+        let mut column = vec![Complex::<f64>::zero(); stft.output_size()];
+        let mut out = vec![0.0f64; 256];
+        let mut recon = Vec::new();
+
+        stft.append_samples(&signal[..]);
+        while stft.contains_enough_to_compute() {
+            stft.compute_complex_column(&mut column[..]);
+            istft.push_complex_column(&column[..]);
+            let n = istft.pop_samples(&mut out[..]);
+            recon.extend_from_slice(&out[..n]);
+            stft.move_to_next_column();
+        }
+
+        // Interior samples (fully overlapped, non-zero window weight) reconstruct
+        // exactly thanks to the sum-of-squares normalization.
+        for i in 20..44 {
+            assert!((recon[i] - signal[i]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_istft_is_cola() {
+        // Rectangular window, window_size a multiple of step_size: every output
+        // sample sees the same number of frames, so COLA holds.
+        assert!(ISTFT::<f64>::new(WindowType::None, 8, 4).is_cola());
+        // step_size that does not divide window_size breaks the tiling.
+        assert!(!ISTFT::<f64>::new(WindowType::None, 8, 3).is_cola());
+    }
+
+    #[test]
+    fn test_periodic_hann_window() {
+        let n = 8;
+        let stft = STFT::<f64>::new_periodic(WindowType::Hanning, n, 4);
+        let window = stft.window.as_ref().unwrap();
+        assert_eq!(window.len(), n);
+        for (i, w) in window.iter().enumerate() {
+            let expected =
+                0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos();
+            assert!((w - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_new_with_window_fn() {
+        let n = 8;
+        let stft = STFT::<f64>::new_with_window_fn(|i, len| i as f64 / len as f64, n, 4);
+        let window = stft.window.as_ref().unwrap();
+        for (i, w) in window.iter().enumerate() {
+            assert_eq!(*w, i as f64 / n as f64);
+        }
+    }
+
+    #[test]
+    fn test_new_with_window_slice() {
+        let custom = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+        let stft = STFT::<f64>::new_with_window_slice(custom.clone(), 8, 4);
+        assert_eq!(stft.window.as_ref().unwrap(), &custom);
+    }
+
+    #[test]
+    fn test_psd_accumulator_dc() {
+        // A constant signal puts all power in the (non-doubled) DC bin. With a
+        // rectangular window, |X_0|^2 = (N*A)^2 and the Welch scale is
+        // 1 / (fs * N), so PSD[0] = N * A^2 / fs.
+        let window_size = 8;
+        let sample_rate = 8;
+        let stft = STFT::new_with_complex_fft(WindowType::None, window_size, window_size);
+        let mut psd = PsdAccumulator::new(stft, sample_rate);
+
+        // Two identical frames exercise the count-weighted running mean.
+        psd.stft.append_samples(&vec![1.0f64; 16][..]);
+        assert!(psd.add_next_column());
+        assert!(psd.add_next_column());
+        assert!(!psd.add_next_column());
+
+        let mut out = vec![0.0; psd.stft.output_size()];
+        psd.mean_psd(&mut out[..]);
+        assert!((out[0] - 1.0).abs() < 1e-9);
+        for v in &out[1..] {
+            assert!(v.abs() < 1e-9);
+        }
+
+        psd.reset();
+        psd.mean_psd(&mut out[..]);
+        for v in &out {
+            assert_eq!(*v, 0.);
+        }
+    }
+
+    #[test]
+    fn test_equivalent_noise_bandwidth() {
+        // Rectangular window: ENBW == 1.
+        let rect = STFT::<f64>::new(WindowType::None, 16, 8);
+        assert!((PsdAccumulator::new(rect, 16).equivalent_noise_bandwidth() - 1.0).abs() < 1e-12);
+
+        // Periodic Hann: ENBW == 1.5.
+        let hann = STFT::<f64>::new_periodic(WindowType::Hanning, 16, 8);
+        assert!((PsdAccumulator::new(hann, 16).equivalent_noise_bandwidth() - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compute_all_matches_streaming() {
+        let window_size = 16;
+        let step_size = 4;
+        let signal: Vec<f64> = (0..64).map(|i| (i as f64 * 0.25).sin()).collect();
+
+        let stft = STFT::new(WindowType::Hanning, window_size, step_size);
+        let multi = stft.compute_all(&signal[..], 4);
+        let single = stft.compute_all(&signal[..], 1);
+
+        // n_frames == 1 + (len - window_size) / step_size
+        assert_eq!(single.len(), 1 + (signal.len() - window_size) / step_size);
+
+        // The threaded split must preserve both frame order and values.
+        assert_eq!(multi.len(), single.len());
+        for (a, b) in multi.iter().zip(&single) {
+            assert_eq!(a.len(), b.len());
+            for (x, y) in a.iter().zip(b) {
+                assert!((x - y).abs() < 1e-12);
+            }
+        }
+
+        // And it must agree with the streaming realfft path (different plan).
+        let mut streaming = STFT::new(WindowType::Hanning, window_size, step_size);
+        streaming.append_samples(&signal[..]);
+        let mut column = vec![0.0; streaming.output_size()];
+        let mut streamed = Vec::new();
+        while streaming.contains_enough_to_compute() {
+            streaming.compute_column(&mut column[..]);
+            streamed.push(column.clone());
+            streaming.move_to_next_column();
+        }
+        assert_eq!(streamed.len(), single.len());
+        for (a, b) in streamed.iter().zip(&single) {
+            for (x, y) in a.iter().zip(b) {
+                assert!((x - y).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mel_filterbank() {
+        // sample_rate == window_size makes bin index equal frequency in Hz.
+        let bank = MelFilterBank::<f64>::new(16, 16, 3, 0., 8.);
+        assert_eq!(bank.n_mels, 3);
+        assert_eq!(bank.filters.len(), 3);
+        // Covers the full window_size / 2 + 1 bins including Nyquist.
+        assert_eq!(bank.output_size, 9);
+
+        // The first triangle spans bins 0..~4, peaking near bin 2.
+        let f0 = &bank.filters[0];
+        assert!((filter_weight(f0, 1) - 0.5025).abs() < 1e-3);
+        assert!((filter_weight(f0, 2) - 0.9950).abs() < 1e-3);
+        assert!((filter_weight(f0, 3) - 0.4943).abs() < 1e-3);
+        // Nothing below the left edge or at/above the right edge.
+        assert_eq!(filter_weight(f0, 0), 0.);
+        assert_eq!(filter_weight(f0, 4), 0.);
+
+        // Every weight is a triangular ramp in (0, 1].
+        for filter in &bank.filters {
+            for &(bin, w) in filter {
+                assert!(bin < bank.output_size);
+                assert!(w > 0. && w <= 1.);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mel_column_matches_filterbank() {
+        let mut stft = STFT::new(WindowType::None, 16, 8);
+        let bank = MelFilterBank::for_stft(&stft, 16, 3, 0., 8.);
+        assert_eq!(bank.output_size, stft.output_size());
+
+        stft.append_samples(&vec![1.0f64; 16][..]);
+        assert!(stft.contains_enough_to_compute());
+        let mut mel = vec![0.; bank.n_mels];
+        stft.compute_mel_column(&bank, &mut mel[..]);
+        // A DC signal lands entirely in bin 0, which no triangle touches, so
+        // every mel band is zero.
+        for m in &mel {
+            assert_eq!(*m, 0.);
+        }
+    }
 }